@@ -0,0 +1,95 @@
+//! Пример широковещательного чата, выросшего из простого эхо-сервера.
+
+use std::net::SocketAddr;
+use tokio::net::TcpListener;
+
+mod server {
+    use futures::{sync::mpsc, Future, Sink, Stream};
+    use std::{
+        collections::HashMap,
+        net::SocketAddr,
+        sync::{Arc, Mutex},
+    };
+    use tokio::codec::{Framed, LinesCodec};
+    use tokio::net::TcpListener;
+
+    /// Карта всех подключённых клиентов. Для каждого адреса храним отправителя в его персональный
+    /// канал: чтобы что-то сказать клиенту, достаточно положить строку в этот канал.
+    type Peers = Arc<Mutex<HashMap<SocketAddr, mpsc::UnboundedSender<String>>>>;
+
+    /// Чат-сервер: всё, что прислал один клиент, рассылается всем остальным.
+    pub fn run(listener: TcpListener) -> impl Future<Item = (), Error = ()> {
+        let peers: Peers = Arc::new(Mutex::new(HashMap::new()));
+        listener
+            .incoming()
+            .map_err(|err| eprintln!("[server] I/O error while accepting connections: {}", err))
+            .for_each(move |connection| {
+                let addr = match connection.peer_addr() {
+                    Ok(addr) => addr,
+                    Err(err) => {
+                        eprintln!("[server] Can't obtain peer address: {}", err);
+                        return Ok(());
+                    }
+                };
+                println!("[server] Peer {} connected", addr);
+                // Оборачиваем сокет в построчный кодек: читаем и пишем целые строки.
+                let (writer, reader) = Framed::new(connection, LinesCodec::new()).split();
+                // Персональный канал этого клиента: сюда другие таски будут класть строки для него.
+                let (tx, rx) = mpsc::unbounded();
+                peers.lock().unwrap().insert(addr, tx);
+
+                let peers_for_read = peers.clone();
+                let peers_for_cleanup = peers.clone();
+
+                // Чтение из сокета: каждую строку раскидываем во все *остальные* каналы.
+                let incoming = reader
+                    .map_err(|err| eprintln!("[server] I/O error while reading: {}", err))
+                    .for_each(move |line| {
+                        let peers = peers_for_read.lock().unwrap();
+                        for (peer_addr, sender) in peers.iter() {
+                            if *peer_addr != addr {
+                                // Если получатель уже отвалился, `unbounded_send` вернёт ошибку,
+                                // но нас это не волнует: его всё равно вычистит его собственная таска.
+                                let _ = sender.unbounded_send(format!("{}: {}", addr, line));
+                            }
+                        }
+                        Ok(())
+                    });
+
+                // Запись в сокет: всё, что пришло в персональный канал, отправляем клиенту.
+                let outgoing = writer
+                    .sink_map_err(|err| eprintln!("[server] I/O error while writing: {}", err))
+                    .send_all(rx)
+                    .map(|_| ());
+
+                // Соединение живёт, пока живы обе стороны. Как только одна из них завершилась
+                // (EOF на чтении или закрытый канал), убираем клиента из карты — его отправитель
+                // дропается, и строки остальных клиентов ему больше не рассылаются.
+                let connection = incoming
+                    .select(outgoing)
+                    .map(|_| ())
+                    .map_err(|_| ())
+                    .then(move |_| {
+                        peers_for_cleanup.lock().unwrap().remove(&addr);
+                        println!("[server] Peer {} disconnected", addr);
+                        Ok(())
+                    });
+
+                // Каждое соединение обрабатываем в отдельной таске.
+                tokio::spawn(connection);
+                Ok(())
+            })
+    }
+}
+
+fn main() {
+    // Указываем порт 0, чтобы операционная система сама назначила свободный порт.
+    let addr: SocketAddr = ([127, 0, 0, 1], 0).into();
+    let listener = TcpListener::bind(&addr).unwrap();
+    let addr = listener.local_addr().unwrap();
+    // Теперь порт уже должен быть ненулевым.
+    assert_ne!(0, addr.port());
+    println!("[server] Listening on {}", addr);
+
+    tokio::run(server::run(listener));
+}
@@ -0,0 +1,106 @@
+//! Пример интерактивного клиента в духе `netcat`: строки из `stdin` уходят в сеть, а ответы
+//! печатаются на экран. Транспорт (TCP или UDP) выбирается аргументом командной строки.
+
+use futures::{sync::mpsc, Stream};
+use std::{env, io, net::SocketAddr, thread};
+
+/// Эта функция возвращает объект типа `Stream`, который можно использовать для асинхронного
+/// получения данных со стандартного ввода (построчно).
+fn input_reader() -> impl Stream<Item = String, Error = ()> {
+    let (sender, receiver) = mpsc::unbounded();
+    thread::spawn(move || {
+        let input = io::stdin();
+        let mut buf = String::new();
+        loop {
+            buf.clear();
+            // Вызов `read_line` заблокирует поток, и именно из-за этого мы и запускаем
+            // считывание в отдельном потоке.
+            if let Err(err) = input.read_line(&mut buf) {
+                eprintln!("Encountered an I/O error while reading from stdin: {}", err);
+                break;
+            }
+            if buf.is_empty() {
+                // Пустой буфер означает, что стандартный ввод закрыт (EOF): выходим из цикла,
+                // единственный `sender` дропается, и `receiver` завершается как поток.
+                break;
+            }
+            if sender.unbounded_send(buf.trim_end().to_string()).is_err() {
+                // Получатель дропнут — продолжать незачем.
+                break;
+            }
+        }
+    });
+    receiver
+}
+
+mod client {
+    use super::input_reader;
+    use futures::{Future, Stream};
+    use std::io::Error;
+    use std::net::SocketAddr;
+    use tokio::codec::{Framed, LinesCodec};
+    use tokio::net::{TcpStream, UdpFramed, UdpSocket};
+
+    /// Строки из `stdin` — это всегда `Stream` с `Error = ()`; приводим его к сетевому
+    /// `Error = io::Error`, чтобы можно было `forward`'ить в сокет.
+    fn stdin_as_io() -> impl Stream<Item = String, Error = Error> {
+        input_reader().map_err(|()| Error::other("stdin reader stopped"))
+    }
+
+    /// TCP-вариант: отправляем строки в соединение и параллельно печатаем всё, что шлёт сервер.
+    pub fn tcp(addr: SocketAddr) -> impl Future<Item = (), Error = ()> {
+        TcpStream::connect(&addr)
+            .and_then(|stream| {
+                let (writer, reader) = Framed::new(stream, LinesCodec::new()).split();
+                // Отправка набранных строк в сокет.
+                let sending = stdin_as_io().forward(writer).map(|_| ());
+                // Печать всего, что пришло от сервера.
+                let receiving = reader.for_each(|line| {
+                    println!("{}", line);
+                    Ok(())
+                });
+                // Клиент завершается, как только закончилось любое из направлений.
+                sending.select(receiving).map(|_| ()).map_err(|(err, _)| err)
+            })
+            .map_err(|err| eprintln!("[client] I/O error: {}", err))
+    }
+
+    /// UDP-вариант: каждая набранная строка уходит одной дейтаграммой на `addr`, а пришедшие
+    /// дейтаграммы печатаются.
+    pub fn udp(addr: SocketAddr) -> impl Future<Item = (), Error = ()> {
+        // Порт 0 — пусть ОС сама выберет локальный адрес для отправки.
+        let local: SocketAddr = ([0, 0, 0, 0], 0).into();
+        let socket = UdpSocket::bind(&local).unwrap();
+        let (sink, stream) = UdpFramed::new(socket, LinesCodec::new()).split();
+        // Каждой строке приклеиваем адрес назначения — этого ждёт `UdpFramed` на отправку.
+        let sending = stdin_as_io()
+            .map(move |line| (line, addr))
+            .forward(sink)
+            .map(|_| ());
+        let receiving = stream.for_each(|(line, from)| {
+            println!("{}: {}", from, line);
+            Ok(())
+        });
+        sending
+            .select(receiving)
+            .map(|_| ())
+            .map_err(|(err, _)| eprintln!("[client] I/O error: {}", err))
+    }
+}
+
+fn main() {
+    // Первый аргумент — транспорт (`tcp` или `udp`), второй — адрес сервера.
+    let transport = env::args().nth(1).unwrap_or_else(|| "tcp".to_string());
+    let addr: SocketAddr = env::args()
+        .nth(2)
+        .unwrap_or_else(|| "127.0.0.1:8080".to_string())
+        .parse()
+        .expect("expected a valid `host:port` address");
+
+    println!("Type lines and hit Enter to send them; send EOF to quit.");
+    match transport.as_str() {
+        "udp" => tokio::run(client::udp(addr)),
+        "tcp" => tokio::run(client::tcp(addr)),
+        other => eprintln!("unknown transport `{}`, expected `tcp` or `udp`", other),
+    }
+}
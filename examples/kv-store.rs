@@ -0,0 +1,109 @@
+//! Пример крошечной сетевой базы данных «ключ-значение» поверх построчного кодека.
+
+use std::net::SocketAddr;
+use tokio::net::TcpListener;
+
+/// Разбор и формирование ответов текстового построчного протокола.
+mod proto {
+    /// Разобранная команда клиента.
+    pub enum Command {
+        /// Получить значение по ключу.
+        Get { key: String },
+        /// Записать значение по ключу.
+        Set { key: String, value: String },
+    }
+
+    impl Command {
+        /// Разбираем строку вида `GET <key>` или `SET <key> <value>`.
+        pub fn parse(line: &str) -> Result<Command, String> {
+            let line = line.trim();
+            let mut parts = line.splitn(2, ' ');
+            match parts.next() {
+                Some("GET") => {
+                    let key = parts.next().ok_or("GET requires a key")?;
+                    Ok(Command::Get {
+                        key: key.trim().to_string(),
+                    })
+                }
+                Some("SET") => {
+                    let rest = parts.next().ok_or("SET requires a key and a value")?;
+                    let mut kv = rest.splitn(2, ' ');
+                    let key = kv.next().ok_or("SET requires a key")?;
+                    let value = kv.next().ok_or("SET requires a value")?;
+                    Ok(Command::Set {
+                        key: key.trim().to_string(),
+                        value: value.to_string(),
+                    })
+                }
+                Some("") | None => Err("empty command".to_string()),
+                Some(other) => Err(format!("unknown command `{}`", other)),
+            }
+        }
+    }
+}
+
+mod server {
+    use super::proto::Command;
+    use futures::{Future, Sink, Stream};
+    use std::{
+        collections::HashMap,
+        sync::{Arc, Mutex},
+    };
+    use tokio::codec::{Framed, LinesCodec};
+    use tokio::net::TcpListener;
+
+    /// Общее для всех соединений хранилище. Так клиенты видят записи друг друга.
+    type Store = Arc<Mutex<HashMap<String, String>>>;
+
+    /// Обрабатываем одну команду, возвращая строку-ответ.
+    fn handle(store: &Store, line: &str) -> String {
+        match Command::parse(line) {
+            Ok(Command::Get { key }) => match store.lock().unwrap().get(&key) {
+                Some(value) => format!("VALUE {}", value),
+                None => format!("ERROR no such key `{}`", key),
+            },
+            Ok(Command::Set { key, value }) => {
+                let previous = store.lock().unwrap().insert(key, value.clone());
+                match previous {
+                    Some(old) => format!("OK was `{}` now `{}`", old, value),
+                    None => format!("OK now `{}`", value),
+                }
+            }
+            Err(err) => format!("ERROR {}", err),
+        }
+    }
+
+    /// Сервер «ключ-значение»: все соединения работают с одним и тем же хранилищем.
+    pub fn echo(listener: TcpListener) -> impl Future<Item = (), Error = ()> {
+        let store: Store = Arc::new(Mutex::new(HashMap::new()));
+        listener
+            .incoming()
+            .for_each(move |connection| {
+                let store = store.clone();
+                let (writer, reader) = Framed::new(connection, LinesCodec::new()).split();
+                // На каждую входящую строку отвечаем строкой-ответом.
+                let responses = reader.map(move |line| handle(&store, &line));
+                let processing = writer
+                    .send_all(responses)
+                    .map(|_| println!("[server] Client disconnected"))
+                    .map_err(|err| {
+                        eprintln!("[server] I/O error while interracting with client: {}", err)
+                    });
+                tokio::spawn(processing);
+                Ok(())
+            })
+            .map_err(|err| eprintln!("[server] I/O error while processing connections: {}", err))
+    }
+}
+
+fn main() {
+    // Указываем порт 0, чтобы операционная система сама назначила свободный порт.
+    let addr: SocketAddr = ([127, 0, 0, 1], 0).into();
+    let listener = TcpListener::bind(&addr).unwrap();
+    let addr = listener.local_addr().unwrap();
+    // Теперь порт уже должен быть ненулевым.
+    assert_ne!(0, addr.port());
+    println!("[server] Listening on {}", addr);
+
+    tokio::run(server::echo(listener));
+}
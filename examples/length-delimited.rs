@@ -0,0 +1,310 @@
+//! Пример кодека с префиксом длины, умеющего переносить сообщения переменного размера.
+
+use futures::Future;
+use std::net::SocketAddr;
+use tokio::net::{TcpListener, TcpStream};
+
+/// В этом модуле живёт кодек с префиксом длины и обёртка-счётчик поверх него.
+mod proto {
+    use bytes::{BufMut, BytesMut};
+    use tokio::codec::{Decoder, Encoder};
+    use tokio::io::{Error, ErrorKind};
+
+    /// Кодек, в котором каждому сообщению предшествует его длина, записанная big-endian'ом.
+    ///
+    /// В отличие от `StatefulCodec` с его жёстко зашитыми 10 байтами, такой кодек умеет переносить
+    /// кадры произвольной длины.
+    pub struct LengthDelimitedCodec {
+        length_field_length: usize,
+        max_frame_length: usize,
+        length_adjustment: isize,
+    }
+
+    /// Строитель для `LengthDelimitedCodec`. Позволяет настроить ширину поля длины, максимальный
+    /// размер кадра и поправку к прочитанной из префикса длине.
+    pub struct Builder {
+        length_field_length: usize,
+        max_frame_length: usize,
+        length_adjustment: isize,
+    }
+
+    impl Builder {
+        /// Значения по умолчанию: 4-байтовый префикс длины и кадры не больше 8 МиБ.
+        pub fn new() -> Self {
+            Builder {
+                length_field_length: 4,
+                max_frame_length: 8 * 1024 * 1024,
+                length_adjustment: 0,
+            }
+        }
+
+        /// Ширина поля длины в байтах. Допустимы значения от 1 до 8 включительно.
+        pub fn length_field_length(mut self, length: usize) -> Self {
+            assert!(
+                (1..=8).contains(&length),
+                "length_field_length must be between 1 and 8 bytes"
+            );
+            self.length_field_length = length;
+            self
+        }
+
+        /// Максимально допустимый размер кадра. Кадры крупнее будут отвергнуты с ошибкой.
+        pub fn max_frame_length(mut self, length: usize) -> Self {
+            self.max_frame_length = length;
+            self
+        }
+
+        /// Поправка, прибавляемая к прочитанной из префикса длине (например, если в длину входит
+        /// само поле длины).
+        pub fn length_adjustment(mut self, adjustment: isize) -> Self {
+            self.length_adjustment = adjustment;
+            self
+        }
+
+        /// Собираем кодек.
+        pub fn build(self) -> LengthDelimitedCodec {
+            LengthDelimitedCodec {
+                length_field_length: self.length_field_length,
+                max_frame_length: self.max_frame_length,
+                length_adjustment: self.length_adjustment,
+            }
+        }
+    }
+
+    impl Default for Builder {
+        fn default() -> Self {
+            Builder::new()
+        }
+    }
+
+    impl LengthDelimitedCodec {
+        /// Кодек с настройками по умолчанию.
+        pub fn new() -> Self {
+            Builder::new().build()
+        }
+
+        /// Читаем big-endian число из первых `self.length_field_length` байт буфера, но пока что
+        /// *не* выкусывая их из `src`.
+        fn peek_length(&self, src: &BytesMut) -> usize {
+            let mut value = 0usize;
+            for &byte in &src[..self.length_field_length] {
+                value = (value << 8) | usize::from(byte);
+            }
+            value
+        }
+    }
+
+    impl Default for LengthDelimitedCodec {
+        fn default() -> Self {
+            LengthDelimitedCodec::new()
+        }
+    }
+
+    impl Decoder for LengthDelimitedCodec {
+        type Item = BytesMut;
+        type Error = Error;
+
+        fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
+            if src.len() < self.length_field_length {
+                // Даже префикс длины ещё не пришёл целиком — ждём ещё байт.
+                return Ok(None);
+            }
+            // Подсматриваем длину, не трогая буфер: вдруг полезной нагрузки ещё нет.
+            let raw = self.peek_length(src) as isize;
+            let payload_len = (raw + self.length_adjustment) as usize;
+            if payload_len > self.max_frame_length {
+                // Верить клиенту на слово и аллоцировать сколько угодно памяти нельзя — это
+                // прямой путь к исчерпанию памяти (DoS), поэтому отвергаем слишком большие кадры.
+                return Err(Error::new(
+                    ErrorKind::InvalidData,
+                    format!(
+                        "frame of length {} exceeds max_frame_length {}",
+                        payload_len, self.max_frame_length
+                    ),
+                ));
+            }
+            let frame_len = self.length_field_length + payload_len;
+            if src.len() < frame_len {
+                // Заголовок есть, а полезной нагрузки ещё не хватает. Резервируем место под
+                // недостающие байты, чтобы не перевыделять буфер на каждом чтении, и ждём.
+                src.reserve(frame_len - src.len());
+                return Ok(None);
+            }
+            // Всё на месте: выкидываем заголовок и отдаём наружу полезную нагрузку.
+            let _header = src.split_to(self.length_field_length);
+            let payload = src.split_to(payload_len);
+            Ok(Some(payload))
+        }
+    }
+
+    impl Encoder for LengthDelimitedCodec {
+        type Item = BytesMut;
+        type Error = Error;
+
+        fn encode(&mut self, item: Self::Item, dst: &mut BytesMut) -> Result<(), Self::Error> {
+            if item.len() > self.max_frame_length {
+                return Err(Error::new(
+                    ErrorKind::InvalidData,
+                    format!(
+                        "frame of length {} exceeds max_frame_length {}",
+                        item.len(),
+                        self.max_frame_length
+                    ),
+                ));
+            }
+            dst.reserve(self.length_field_length + item.len());
+            // Записываем длину big-endian'ом, беря только младшие `length_field_length` байт.
+            let len = item.len();
+            for i in (0..self.length_field_length).rev() {
+                dst.put_u8((len >> (8 * i)) as u8);
+            }
+            dst.put(item);
+            Ok(())
+        }
+    }
+
+    /// Обёртка-счётчик поверх любого кодека: считает, сколько сообщений прошло в каждую сторону.
+    ///
+    /// Так пример со счётчиком сообщений продолжает работать, но теперь уже на кадрах переменной
+    /// длины, а не на жёстко зашитых десятибайтовых.
+    pub struct Counting<C> {
+        inner: C,
+        /// Счётчик отправленных сообщений.
+        pub sent_counter: usize,
+        /// Счётчик полученных сообщений.
+        pub received_counter: usize,
+    }
+
+    impl<C> Counting<C> {
+        /// Оборачиваем кодек счётчиком.
+        pub fn new(inner: C) -> Self {
+            Counting {
+                inner,
+                sent_counter: 0,
+                received_counter: 0,
+            }
+        }
+    }
+
+    impl<C: Decoder> Decoder for Counting<C> {
+        type Item = C::Item;
+        type Error = C::Error;
+
+        fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
+            match self.inner.decode(src)? {
+                Some(item) => {
+                    self.received_counter += 1;
+                    Ok(Some(item))
+                }
+                None => Ok(None),
+            }
+        }
+    }
+
+    impl<C: Encoder> Encoder for Counting<C> {
+        type Item = C::Item;
+        type Error = C::Error;
+
+        fn encode(&mut self, item: Self::Item, dst: &mut BytesMut) -> Result<(), Self::Error> {
+            self.inner.encode(item, dst)?;
+            self.sent_counter += 1;
+            Ok(())
+        }
+    }
+}
+
+mod server {
+    use super::proto::{Builder, Counting};
+    use futures::{Future, Sink, Stream};
+    use tokio::codec::Framed;
+    use tokio::net::TcpListener;
+
+    /// Простенький эхо-сервер, но уже на кадрах переменной длины.
+    pub fn echo(listener: TcpListener) -> impl Future<Item = (), Error = ()> {
+        listener
+            .incoming()
+            .for_each(|connection| {
+                // Настраиваем кодек явно (а не берём настройки по умолчанию), чтобы показать,
+                // как им вообще пользоваться: двухбайтовый префикс длины, кадры не больше
+                // килобайта, без поправки к прочитанной длине.
+                let codec = Counting::new(
+                    Builder::new()
+                        .length_field_length(2)
+                        .max_frame_length(1024)
+                        .length_adjustment(0)
+                        .build(),
+                );
+                let (writer, reader) = Framed::new(connection, codec).split();
+                let processing = writer
+                    .send_all(reader.inspect(|_| println!("[server] Got a message from client")))
+                    .map(|_| println!("[server] Client disconnected"))
+                    .map_err(|err| {
+                        eprintln!("[server] I/O error while interracting with client: {}", err)
+                    });
+                tokio::spawn(processing);
+                Ok(())
+            })
+            .map_err(|err| eprintln!("[server] I/O error while processing connections: {}", err))
+    }
+}
+
+mod client {
+    use super::proto::{Builder, Counting};
+    use bytes::BytesMut;
+    use futures::{Future, Sink, Stream};
+    use tokio::codec::Framed;
+    use tokio::net::TcpStream;
+
+    /// Отправляем серверу несколько кадров разной длины и ждём их обратно.
+    pub fn run(connection: TcpStream) -> impl Future<Item = (), Error = ()> {
+        // Настройки кодека должны совпадать с серверными, иначе обе стороны разъедутся
+        // в том, сколько байт занимает префикс длины.
+        let codec = Counting::new(
+            Builder::new()
+                .length_field_length(2)
+                .max_frame_length(1024)
+                .length_adjustment(0)
+                .build(),
+        );
+        let (writer, reader) = Framed::new(connection, codec).split();
+        // Набор сообщений заведомо разной длины — как раз то, что старый кодек не осилил бы.
+        let messages = vec![
+            BytesMut::from(&b"hi"[..]),
+            BytesMut::from(&b"length-delimited"[..]),
+            BytesMut::from(&b"!"[..]),
+        ];
+        let total = messages.len();
+        writer
+            .send_all(futures::stream::iter_ok::<_, std::io::Error>(messages))
+            .and_then(move |(writer, _)| {
+                // Дочитываем ответы и закрываем соединение.
+                reader.take(total as u64).for_each(|frame| {
+                    println!("[client] Received {} bytes back", frame.len());
+                    Ok(())
+                })
+                // `writer` держим до конца чтения, чтобы сокет не закрылся раньше времени.
+                .map(move |_| drop(writer))
+            })
+            .map_err(|err| eprintln!("[client] I/O error: {}", err))
+    }
+}
+
+fn main() {
+    // Указываем порт 0, чтобы операционная система сама назначила свободный порт.
+    let addr: SocketAddr = ([127, 0, 0, 1], 0).into();
+    let listener = TcpListener::bind(&addr).unwrap();
+    let addr = listener.local_addr().unwrap();
+    // Теперь порт уже должен быть ненулевым.
+    assert_ne!(0, addr.port());
+
+    let srv = server::echo(listener);
+    let client = TcpStream::connect(&addr)
+        .map_err(|err| eprintln!("[client] Can't connect: {}", err))
+        .and_then(client::run);
+
+    tokio::run(
+        srv.select(client)
+            .map(|((), _select_next_future)| ())
+            .map_err(|((), _select_next_future)| ()),
+    );
+}
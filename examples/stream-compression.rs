@@ -0,0 +1,151 @@
+//! Пример выноса CPU-тяжёлой работы (сжатия) с реакторных потоков на отдельный пул воркеров.
+
+use std::{env, net::SocketAddr};
+use tokio::net::TcpListener;
+
+/// Пул воркеров, на котором синхронно крутится собственно сжатие/расжатие, чтобы не блокировать
+/// событийный цикл.
+mod pool {
+    use bytes::Bytes;
+    use flate2::write::{DeflateDecoder, DeflateEncoder};
+    use flate2::Compression;
+    use futures::sync::oneshot;
+    use std::io::{self, Write};
+    use std::sync::{mpsc, Arc, Mutex};
+    use std::thread;
+
+    /// Что именно делает пул с каждым кадром.
+    #[derive(Clone, Copy)]
+    pub enum Mode {
+        /// Сжимаем входящие кадры.
+        Compress,
+        /// Расжимаем входящие кадры.
+        Decompress,
+    }
+
+    /// Задание для воркера: входные байты и канал, по которому вернётся результат.
+    type Job = (Bytes, oneshot::Sender<io::Result<Bytes>>);
+
+    /// Синхронно обрабатываем один кадр. Эта функция и есть то «тяжёлое», ради чего затеян пул.
+    ///
+    /// В отличие от сжатия, расжатие — это разбор данных, присланных клиентом, и может вполне
+    /// законно споткнуться об испорченный поток, так что результат — `Result`, а не паника.
+    fn process(mode: Mode, input: &[u8]) -> io::Result<Bytes> {
+        match mode {
+            Mode::Compress => {
+                let mut encoder = DeflateEncoder::new(Vec::new(), Compression::default());
+                encoder.write_all(input).expect("writing to a Vec never fails");
+                Ok(Bytes::from(encoder.finish().expect("finishing a Vec never fails")))
+            }
+            Mode::Decompress => {
+                let mut decoder = DeflateDecoder::new(Vec::new());
+                // В отличие от сжатия, тут `write_all` сам разбирает deflate-поток и вернёт
+                // ошибку на испорченных данных — и это НЕ баг вызывающего кода, а вполне
+                // ожидаемая ситуация, которую нельзя давить паникой в таске воркера (иначе
+                // воркер умрёт навсегда, а не пропадёт только этот кадр).
+                decoder.write_all(input)?;
+                Ok(Bytes::from(decoder.finish().expect("finishing a Vec never fails")))
+            }
+        }
+    }
+
+    /// Пул из фиксированного числа потоков, которые тянут задания из общей очереди.
+    pub struct Pool {
+        tx: mpsc::Sender<Job>,
+    }
+
+    impl Pool {
+        /// Запускаем `size` потоков-воркеров, каждый из которых работает в режиме `mode`.
+        pub fn new(size: usize, mode: Mode) -> Pool {
+            let (tx, rx) = mpsc::channel::<Job>();
+            // Единственный получатель делим между потоками под мьютексом: кто первый схватил
+            // блокировку, тот и берёт следующее задание.
+            let rx = Arc::new(Mutex::new(rx));
+            for _ in 0..size {
+                let rx = rx.clone();
+                thread::spawn(move || loop {
+                    let job = rx.lock().unwrap().recv();
+                    match job {
+                        Ok((bytes, reply)) => {
+                            // Получателя могли дропнуть (соединение закрылось) — это не ошибка.
+                            let _ = reply.send(process(mode, &bytes));
+                        }
+                        // Все отправители дропнуты — пора закругляться.
+                        Err(_) => break,
+                    }
+                });
+            }
+            Pool { tx }
+        }
+
+        /// Кладём кадр в очередь и сразу получаем фьючу с будущим результатом.
+        pub fn submit(&self, bytes: Bytes) -> oneshot::Receiver<io::Result<Bytes>> {
+            let (reply_tx, reply_rx) = oneshot::channel();
+            // Если все воркеры умерли, receiver просто резолвится в ошибку `Canceled`.
+            let _ = self.tx.send((bytes, reply_tx));
+            reply_rx
+        }
+    }
+}
+
+mod server {
+    use super::pool::{Mode, Pool};
+    use futures::{Future, Sink, Stream};
+    use std::io::Error;
+    use std::sync::Arc;
+    use tokio::codec::{Framed, LengthDelimitedCodec};
+    use tokio::net::TcpListener;
+
+    /// Сервер, вставляющий между чтением и записью стадию (рас)сжатия на пуле воркеров.
+    pub fn run(
+        listener: TcpListener,
+        mode: Mode,
+        pool_size: usize,
+    ) -> impl Future<Item = (), Error = ()> {
+        let pool = Arc::new(Pool::new(pool_size, mode));
+        listener
+            .incoming()
+            .map_err(|err| eprintln!("[server] I/O error while accepting connections: {}", err))
+            .for_each(move |connection| {
+                let pool = pool.clone();
+                let (writer, reader) = Framed::new(connection, LengthDelimitedCodec::new()).split();
+                // На каждый кадр заводим задание в пуле и получаем фьючу с результатом. `buffered`
+                // даёт воркерам работать параллельно, но отдаёт результаты строго по порядку.
+                let transformed = reader
+                    .map(move |frame| {
+                        pool.submit(frame.freeze())
+                            .map_err(|_| Error::other("compression worker pool is gone"))
+                            // Если воркер успел ответить, его результатом может быть и ошибка
+                            // расжатия (`process` больше не паникует на испорченных данных) —
+                            // разворачиваем её сюда же, чтобы она закрыла только это соединение.
+                            .and_then(|result| result)
+                    })
+                    .buffered(pool_size);
+                let processing = writer
+                    .send_all(transformed)
+                    .map(|_| println!("[server] Client disconnected"))
+                    .map_err(|err| eprintln!("[server] I/O error: {}", err));
+                tokio::spawn(processing);
+                Ok(())
+            })
+    }
+}
+
+fn main() {
+    // Указываем порт 0, чтобы операционная система сама назначила свободный порт.
+    let addr: SocketAddr = ([127, 0, 0, 1], 0).into();
+    let listener = TcpListener::bind(&addr).unwrap();
+    let addr = listener.local_addr().unwrap();
+    // Теперь порт уже должен быть ненулевым.
+    assert_ne!(0, addr.port());
+    println!("[server] Listening on {}", addr);
+
+    // Режим выбирается первым аргументом командной строки: по умолчанию сжимаем, но можно
+    // запустить и на расжатие.
+    let mode = match env::args().nth(1).as_deref() {
+        Some("decompress") => pool::Mode::Decompress,
+        _ => pool::Mode::Compress,
+    };
+    // Размер пула в настоящем примере тоже брался бы из аргументов командной строки.
+    tokio::run(server::run(listener, mode, 4));
+}
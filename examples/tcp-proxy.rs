@@ -0,0 +1,100 @@
+//! Пример прозрачного двунаправленного TCP-прокси.
+
+use std::net::SocketAddr;
+use tokio::net::TcpListener;
+
+mod proxy {
+    use futures::future::Either;
+    use futures::{Future, Stream};
+    use std::net::SocketAddr;
+    use tokio::io::{copy, shutdown, AsyncRead, Error};
+    use tokio::net::{TcpListener, TcpStream};
+
+    /// Прокидываем одно входящее соединение на фиксированный upstream.
+    fn proxy_connection(
+        inbound: TcpStream,
+        upstream: SocketAddr,
+    ) -> impl Future<Item = (), Error = ()> {
+        TcpStream::connect(&upstream)
+            .and_then(move |outbound| {
+                // Делим оба сокета на читающую и пишущую половины.
+                let (client_reader, client_writer) = inbound.split();
+                let (upstream_reader, upstream_writer) = outbound.split();
+                // Клиент -> upstream и upstream -> клиент копируем одновременно.
+                let client_to_upstream = copy(client_reader, upstream_writer);
+                let upstream_to_client = copy(upstream_reader, client_writer);
+                // `join` дождался бы завершения ОБОИХ копирований, а значит наполовину
+                // закрытое соединение (клиент закрыл запись, а сервер ещё отвечает) никогда
+                // не пробросило бы EOF дальше. Поэтому используем `select2`: как только
+                // завершается одно из направлений, явно закрываем (`shutdown`) пишущую
+                // половину другого сокета, чтобы половинное закрытие дошло до второй стороны,
+                // а затем дожидаемся, пока закончится и оставшееся копирование.
+                client_to_upstream
+                    .select2(upstream_to_client)
+                    // `select2` возвращает ошибку вместе с ещё не завершившейся фьючей
+                    // противоположного направления; она нам больше не нужна, так что просто
+                    // вытаскиваем саму ошибку ввода-вывода.
+                    .map_err(|err| match err {
+                        Either::A((err, _)) => err,
+                        Either::B((err, _)) => err,
+                    })
+                    .and_then(
+                        |result| -> Box<dyn Future<Item = (u64, u64), Error = Error> + Send> {
+                            match result {
+                                Either::A((
+                                    (to_upstream, _client_reader, upstream_writer),
+                                    rest,
+                                )) => Box::new(
+                                    shutdown(upstream_writer)
+                                        .and_then(|_| rest)
+                                        .map(move |(to_client, _, _)| (to_upstream, to_client)),
+                                ),
+                                Either::B((
+                                    (to_client, _upstream_reader, client_writer),
+                                    rest,
+                                )) => Box::new(
+                                    shutdown(client_writer)
+                                        .and_then(|_| rest)
+                                        .map(move |(to_upstream, _, _)| (to_upstream, to_client)),
+                                ),
+                            }
+                        },
+                    )
+            })
+            .map(|(to_upstream, to_client)| {
+                println!(
+                    "[proxy] Connection finished: {} bytes client->upstream, {} bytes upstream->client",
+                    to_upstream, to_client
+                );
+            })
+            // Ошибку логируем, но listener из-за неё не роняем.
+            .map_err(|err| eprintln!("[proxy] I/O error while proxying connection: {}", err))
+    }
+
+    /// Принимаем соединения и каждое прокидываем на `upstream`.
+    pub fn run(listener: TcpListener, upstream: SocketAddr) -> impl Future<Item = (), Error = ()> {
+        listener
+            .incoming()
+            .map_err(|err| eprintln!("[proxy] I/O error while accepting connections: {}", err))
+            .for_each(move |inbound| {
+                // Каждое соединение обрабатываем в отдельной таске, чтобы ошибка на одном
+                // соединении не мешала остальным.
+                tokio::spawn(proxy_connection(inbound, upstream));
+                Ok(())
+            })
+    }
+}
+
+fn main() {
+    // Указываем порт 0, чтобы операционная система сама назначила свободный порт.
+    let addr: SocketAddr = ([127, 0, 0, 1], 0).into();
+    let listener = TcpListener::bind(&addr).unwrap();
+    let addr = listener.local_addr().unwrap();
+    // Теперь порт уже должен быть ненулевым.
+    assert_ne!(0, addr.port());
+    // В настоящем примере upstream задавался бы аргументом; здесь для простоты берём фиксированный.
+    let upstream: SocketAddr = ([127, 0, 0, 1], 8080).into();
+    println!("[proxy] Listening on {}, forwarding to {}", addr, upstream);
+
+    tokio::run(proxy::run(listener, upstream));
+}
@@ -0,0 +1,73 @@
+//! Пример UDP-эхо-сервера: аналог TCP-примеров с кодеком, но без соединений.
+
+use std::net::SocketAddr;
+use tokio::net::UdpSocket;
+
+/// Кодек для дейтаграмм: работает с целыми пакетами, а не с потоком байт.
+mod proto {
+    use bytes::{BufMut, BytesMut};
+    use tokio::codec::{Decoder, Encoder};
+    use tokio::io::Error;
+
+    /// Простейший кодек, в котором один кадр — это одна дейтаграмма целиком.
+    pub struct DatagramCodec;
+
+    impl Decoder for DatagramCodec {
+        type Item = BytesMut;
+        type Error = Error;
+
+        fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
+            if src.is_empty() {
+                // Пустых дейтаграмм наружу не отдаём — ждать тут всё равно нечего.
+                return Ok(None);
+            }
+            // Забираем весь пакет целиком: в UDP границы сообщений совпадают с границами кадров.
+            let len = src.len();
+            Ok(Some(src.split_to(len)))
+        }
+    }
+
+    impl Encoder for DatagramCodec {
+        type Item = BytesMut;
+        type Error = Error;
+
+        fn encode(&mut self, item: Self::Item, dst: &mut BytesMut) -> Result<(), Self::Error> {
+            dst.reserve(item.len());
+            dst.put(item);
+            Ok(())
+        }
+    }
+}
+
+mod server {
+    use super::proto::DatagramCodec;
+    use futures::{Future, Stream};
+    use tokio::net::{UdpFramed, UdpSocket};
+
+    /// Эхо-сервер на UDP. Соединений здесь нет: это одна пара `Stream`/`Sink`, в которую каждый
+    /// прочитанный пакет отправляется обратно на адрес отправителя.
+    pub fn echo(socket: UdpSocket) -> impl Future<Item = (), Error = ()> {
+        // `UdpFramed` раздаёт пары `(кадр, адрес отправителя)` и принимает такие же на отправку.
+        let (sink, stream) = UdpFramed::new(socket, DatagramCodec).split();
+        stream
+            .inspect(|(datagram, addr)| {
+                println!("[server] Got {} bytes from {}", datagram.len(), addr)
+            })
+            // Каждую дейтаграмму шлём ровно туда, откуда она пришла.
+            .forward(sink)
+            .map(|_| ())
+            .map_err(|err| eprintln!("[server] I/O error: {}", err))
+    }
+}
+
+fn main() {
+    // Указываем порт 0, чтобы операционная система сама назначила свободный порт.
+    let addr: SocketAddr = ([127, 0, 0, 1], 0).into();
+    let socket = UdpSocket::bind(&addr).unwrap();
+    let addr = socket.local_addr().unwrap();
+    // Теперь порт уже должен быть ненулевым.
+    assert_ne!(0, addr.port());
+    println!("[server] Listening on {}", addr);
+
+    tokio::run(server::echo(socket));
+}